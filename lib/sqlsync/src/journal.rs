@@ -1,7 +1,23 @@
 use std::{ops::Range, slice::Iter};
 
+use thiserror::Error;
+
 pub type LSN = u64;
 
+/// Identifies a single journal when many are multiplexed over one sync connection.
+/// Matches `crate::journal::journal::JournalId`'s underlying type so a journal
+/// id round-trips between the two without reinterpreting its sign.
+pub type JournalId = i32;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum WriteError {
+    /// `batch.start` is past the end of the journal, i.e. there's a gap
+    /// between what we have and what the batch would add. The caller needs
+    /// to go fetch the missing range before retrying.
+    #[error("write would leave a gap: journal ends at {journal_end}, batch starts at {batch_start}")]
+    Gap { journal_end: LSN, batch_start: LSN },
+}
+
 /// A Cursor represents a pointer to a position in the log (LSN)
 pub struct Cursor {
     lsn: LSN,
@@ -11,6 +27,10 @@ impl Cursor {
     pub fn new(lsn: LSN) -> Self {
         Self { lsn }
     }
+
+    pub fn lsn(&self) -> LSN {
+        self.lsn
+    }
 }
 
 pub struct Batch<'a, T> {
@@ -18,10 +38,17 @@ pub struct Batch<'a, T> {
     data: &'a [T],
 }
 
+impl<'a, T> Batch<'a, T> {
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
 pub struct Journal<T>
 where
     T: Clone,
 {
+    id: JournalId,
     /// The range of LSNs covered by this journal.
     /// The journal is guaranteed to contain all LSNs in the range [start, end).
     range: Range<LSN>,
@@ -32,13 +59,23 @@ impl<T> Journal<T>
 where
     T: Clone,
 {
-    pub fn new() -> Self {
+    pub fn new(id: JournalId) -> Self {
         Self {
+            id,
             range: 0..0,
             data: Vec::new(),
         }
     }
 
+    pub fn id(&self) -> JournalId {
+        self.id
+    }
+
+    /// The range of LSNs this journal currently covers.
+    pub fn range(&self) -> Range<LSN> {
+        self.range.clone()
+    }
+
     /// Return a cursor pointing at the last entry in the journal.
     pub fn end(&self) -> anyhow::Result<Cursor> {
         if self.data.is_empty() {
@@ -55,14 +92,33 @@ where
     }
 
     /// Merge a batch into the journal starting at batch.start and possibly extending the journal.
-    /// The batch must overlap with the journal or be immediately after the journal.
-    /// Note: this method does not replace existing entries in the journal, it only extends the journal if needed.
-    pub fn write(&mut self, batch: Batch<T>) {
-        assert!(batch.start >= self.range.start);
-        assert!(batch.start <= self.range.end);
-        let offset = self.range.end - batch.start;
-        self.data.extend_from_slice(&batch.data[offset as usize..]);
-        self.range.end = batch.start + batch.data.len() as LSN;
+    /// The batch must overlap with the journal or be immediately after the journal, otherwise
+    /// `WriteError::Gap` is returned rather than panicking.
+    /// Note: this method does not replace existing entries in the journal, it only extends the
+    /// journal if needed, so writing a batch that overlaps entries we already have is a no-op
+    /// for that overlapping prefix (idempotent). A batch that starts before `range.start` is
+    /// similarly treated as idempotent: the prefix we've already rolled past is skipped rather
+    /// than asserted against, since a peer that hasn't rolled up yet will legitimately resend it.
+    pub fn write(&mut self, batch: Batch<T>) -> Result<(), WriteError> {
+        if batch.start > self.range.end {
+            return Err(WriteError::Gap {
+                journal_end: self.range.end,
+                batch_start: batch.start,
+            });
+        }
+        let skip = self.range.start.saturating_sub(batch.start) as usize;
+        if skip >= batch.data.len() {
+            // The whole batch falls before our start, i.e. we've already rolled it up.
+            return Ok(());
+        }
+        let start = batch.start + skip as LSN;
+        let data = &batch.data[skip..];
+        let offset = self.range.end - start;
+        if (offset as usize) < data.len() {
+            self.data.extend_from_slice(&data[offset as usize..]);
+            self.range.end = start + data.len() as LSN;
+        }
+        Ok(())
     }
 
     /// Read a batch of entries from the journal starting at cursor.
@@ -1,5 +1,5 @@
 use std::fmt::Debug;
-use std::io;
+use std::io::{self, Read, Seek, Write};
 use std::result::Result;
 
 use thiserror::Error;
@@ -21,20 +21,451 @@ pub enum JournalError {
 
     #[error("failed to serialize object")]
     SerializationError(#[source] io::Error),
+
+    #[error("corrupt entry at lsn {lsn}: {reason}")]
+    CorruptEntry { lsn: Lsn, reason: &'static str },
+
+    #[error("journal is poisoned by a previous io error and must be repaired before reuse")]
+    PreviousIo,
 }
 
 pub type JournalResult<T> = Result<T, JournalError>;
 
+/// Controls how [`scan_entries`] reacts to a torn or corrupted tail entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryMode {
+    /// Truncate at the first invalid entry and keep whatever was valid before it.
+    /// This is the right default for `open()`: a torn write during an unclean
+    /// shutdown is expected, not exceptional.
+    BestEffort,
+    /// Return `JournalError::CorruptEntry` instead of truncating.
+    Strict,
+}
+
+impl Default for RecoveryMode {
+    fn default() -> Self {
+        RecoveryMode::BestEffort
+    }
+}
+
+/// On-disk framing for a single journal entry:
+///
+/// ```text
+/// [ len: u32 ][ checksum_a: u32 ][ checksum_b: u32 ]   <- header
+/// [ ...len bytes of payload... ]
+/// [ checksum_a: u32 ][ checksum_b: u32 ]                <- trailer
+/// ```
+///
+/// The header and trailer each carry the same checksum pair; an entry is only
+/// considered durable if the header and trailer agree with each other *and*
+/// with the checksums recomputed over the payload that was actually read.
+/// This double-buffer scheme lets `open()` detect a torn tail write (where the
+/// header was flushed but the trailer wasn't, or vice versa) without needing a
+/// separate fsync barrier between header and payload.
+pub const ENTRY_HEADER_LEN: usize = 4 + 4 + 4;
+pub const ENTRY_TRAILER_LEN: usize = 4 + 4;
+
+/// Upper bound on a single entry's payload length. A torn or corrupted
+/// header's `len` field must be checked against this *before* it is used to
+/// allocate anything: a garbled tail header can otherwise claim up to 4 GiB,
+/// which is exactly the kind of input the crash-recovery scan is meant to
+/// survive (and would abort outright on a 32-bit/wasm target).
+pub const MAX_ENTRY_LEN: usize = 16 * 1024 * 1024;
+
+/// Write `payload` to `writer` using the framing described above.
+pub fn write_entry<W: Write>(writer: &mut W, payload: &[u8]) -> io::Result<()> {
+    let (a, b) = checksum_pair(payload);
+    writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+    writer.write_all(&a.to_be_bytes())?;
+    writer.write_all(&b.to_be_bytes())?;
+    writer.write_all(payload)?;
+    writer.write_all(&a.to_be_bytes())?;
+    writer.write_all(&b.to_be_bytes())?;
+    Ok(())
+}
+
+/// Read and validate a single framed entry, returning its payload.
+/// Returns `Ok(None)` on a clean EOF (no header present at all).
+///
+/// A torn read (a short header or trailer that isn't a clean entry
+/// boundary) is routed through [`corrupt`] rather than propagated as an
+/// `io::Error`, so `RecoveryMode::BestEffort` truncates at that entry
+/// instead of failing the whole scan; a genuine I/O error (as opposed to
+/// a short read caused by hitting the end of the journal) still
+/// propagates, since that's not something recovery should paper over.
+fn read_entry<R: Read>(reader: &mut R, lsn: Lsn, mode: RecoveryMode) -> JournalResult<Option<Vec<u8>>> {
+    let mut header = [0u8; ENTRY_HEADER_LEN];
+    match read_exact_or_eof(reader, &mut header) {
+        Ok(false) => return Ok(None),
+        Ok(true) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            return corrupt(lsn, mode, "entry header runs past end of journal");
+        }
+        Err(e) => return Err(JournalError::IoError(e)),
+    }
+
+    let len = u32::from_be_bytes(header[0..4].try_into().unwrap()) as usize;
+    let header_checksum = (
+        u32::from_be_bytes(header[4..8].try_into().unwrap()),
+        u32::from_be_bytes(header[8..12].try_into().unwrap()),
+    );
+
+    if len > MAX_ENTRY_LEN {
+        return corrupt(lsn, mode, "entry length exceeds MAX_ENTRY_LEN");
+    }
+
+    let mut payload = vec![0u8; len];
+    match reader.read_exact(&mut payload) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            return corrupt(lsn, mode, "entry runs past end of journal");
+        }
+        Err(e) => return Err(JournalError::IoError(e)),
+    }
+
+    let mut trailer = [0u8; ENTRY_TRAILER_LEN];
+    match read_exact_or_eof(reader, &mut trailer) {
+        Ok(true) => {}
+        Ok(false) => return corrupt(lsn, mode, "entry runs past end of journal"),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            return corrupt(lsn, mode, "entry trailer runs past end of journal");
+        }
+        Err(e) => return Err(JournalError::IoError(e)),
+    }
+
+    let trailer_checksum = (
+        u32::from_be_bytes(trailer[0..4].try_into().unwrap()),
+        u32::from_be_bytes(trailer[4..8].try_into().unwrap()),
+    );
+    let computed_checksum = checksum_pair(&payload);
+
+    if header_checksum != trailer_checksum || header_checksum != computed_checksum {
+        return corrupt(lsn, mode, "header/trailer checksum mismatch");
+    }
+
+    Ok(Some(payload))
+}
+
+fn corrupt(lsn: Lsn, mode: RecoveryMode, reason: &'static str) -> JournalResult<Option<Vec<u8>>> {
+    match mode {
+        RecoveryMode::Strict => Err(JournalError::CorruptEntry { lsn, reason }),
+        RecoveryMode::BestEffort => Ok(None),
+    }
+}
+
+/// Scan forward from the start of a journal, validating each entry in turn.
+/// Stops at the first entry that is missing, truncated, or fails its checksum
+/// and returns the entries read so far along with the recovered end `Lsn`
+/// (i.e. the lsn one past the last valid entry). In `RecoveryMode::Strict`
+/// a corrupt entry is surfaced as `JournalError::CorruptEntry` instead of
+/// silently ending the scan.
+pub fn scan_entries<R: Read>(
+    mut reader: R,
+    start: Lsn,
+    mode: RecoveryMode,
+) -> JournalResult<(Vec<Vec<u8>>, Lsn)> {
+    let mut entries = Vec::new();
+    let mut lsn = start;
+
+    loop {
+        match read_entry(&mut reader, lsn, mode)? {
+            Some(payload) => {
+                entries.push(payload);
+                lsn += 1;
+            }
+            None => break,
+        }
+    }
+
+    Ok((entries, lsn))
+}
+
+/// Read exactly `buf.len()` bytes, returning `Ok(false)` if the reader was
+/// already at a clean EOF before any bytes were read, and `Ok(true)` once
+/// `buf` is fully populated. Any other short read is surfaced via `Err`,
+/// since it indicates a torn write rather than a well-formed end of journal.
+fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            Ok(n) => read += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+/// Two independent checksums over the same bytes; an entry is rejected by
+/// `scan_entries` if either one disagrees with its stored counterparts. A
+/// torn write and a bit flip are reported the same way.
+fn checksum_pair(data: &[u8]) -> (u32, u32) {
+    (crc32(data), fletcher32(data))
+}
+
+/// Basic CRC-32 (IEEE 802.3 polynomial), computed bitwise rather than via a
+/// lookup table since entries are small and this keeps the module dependency-free.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Fletcher-32 checksum, used as the second, algorithmically-independent
+/// checksum in the header/trailer pair.
+fn fletcher32(data: &[u8]) -> u32 {
+    let mut sum1: u32 = 0xFFFF;
+    let mut sum2: u32 = 0xFFFF;
+
+    for chunk in data.chunks(359) {
+        for &byte in chunk {
+            sum1 += byte as u32;
+            sum2 += sum1;
+        }
+        sum1 %= 0xFFFF;
+        sum2 %= 0xFFFF;
+    }
+
+    (sum2 << 16) | sum1
+}
+
+/// Shared helper for implementations to latch the "poisoned" state described
+/// by [`Journal::is_poisoned`]. An implementation embeds this and calls
+/// [`PoisonFlag::check`] at the top of every fallible method, and
+/// [`PoisonFlag::set`] wherever an `io::Error` is observed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PoisonFlag(bool);
+
+impl PoisonFlag {
+    pub fn set(&mut self) {
+        self.0 = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = false;
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.0
+    }
+
+    /// Returns `Err(JournalError::PreviousIo)` if the flag is set, otherwise `Ok(())`.
+    pub fn check(&self) -> JournalResult<()> {
+        if self.0 {
+            Err(JournalError::PreviousIo)
+        } else {
+            Ok(())
+        }
+    }
+}
+
 pub trait Journal: Syncable + Scannable + Debug + Sized {
+    /// Byte-addressable backing storage this implementation frames entries
+    /// into and out of via [`write_entry`]/[`scan_entries`] (a `File`, an
+    /// in-memory buffer, etc).
+    type Storage: Read + Write + Seek;
+
+    /// Open the journal, scanning forward from its start to find the durable
+    /// end of the log. A torn or corrupted tail entry is truncated rather
+    /// than surfaced as an error; use [`RecoveryMode::Strict`] semantics via
+    /// [`Journal::recover`] if that entry should instead be reported to the
+    /// caller.
+    ///
+    /// Implementations must refuse to reopen a journal that was left
+    /// poisoned by a previous io error (see [`Journal::is_poisoned`]),
+    /// returning `JournalError::PreviousIo` instead. Call [`Journal::repair`]
+    /// first to clear the poison before reopening.
     fn open(id: JournalId) -> JournalResult<Self>;
 
     // TODO: eventually this needs to be a UUID of some kind
     /// this journal's id
     fn id(&self) -> JournalId;
 
-    /// append a new journal entry, and then write to it
-    fn append(&mut self, obj: impl Serializable) -> JournalResult<()>;
+    /// This implementation's backing storage, for [`Journal::recover`],
+    /// [`Journal::append`], and [`Journal::drop_prefix`] to frame entries
+    /// into.
+    fn storage_mut(&mut self) -> &mut Self::Storage;
+
+    /// This implementation's poison latch. Required so the default methods
+    /// below can check and set it on every implementation's behalf.
+    fn poison(&self) -> &PoisonFlag;
+    fn poison_mut(&mut self) -> &mut PoisonFlag;
+
+    fn recovered_end_lsn(&self) -> Lsn;
+    fn set_recovered_end_lsn(&mut self, lsn: Lsn);
+
+    /// The end of the durable, validated portion of the journal, as
+    /// determined by the crash-recovery scan performed during `open`.
+    /// Replay should never proceed past this point.
+    fn recovered_end(&self) -> Lsn {
+        self.recovered_end_lsn()
+    }
+
+    /// True once an underlying `io::Error` has been observed by `append`,
+    /// `drop_prefix`, or any `Syncable`/`Scannable` operation. Once poisoned,
+    /// every method on the journal must short-circuit with
+    /// `JournalError::PreviousIo` rather than touching storage again, since
+    /// the journal may already hold a half-written entry.
+    fn is_poisoned(&self) -> bool {
+        self.poison().is_set()
+    }
+
+    /// Explicitly clear the poison flag after verifying (or repairing) the
+    /// on-disk state out of band. This is the only sanctioned way back to a
+    /// usable journal once `is_poisoned()` is true; there is no implicit
+    /// recovery on `open`.
+    fn repair(&mut self) -> JournalResult<()> {
+        self.poison_mut().clear();
+        Ok(())
+    }
+
+    /// Re-scan storage from the start with [`scan_entries`] and refresh the
+    /// recovered-end lsn. `open()` implementations should call this (with
+    /// `RecoveryMode::BestEffort`) right after constructing storage, before
+    /// returning `Self`.
+    fn recover(&mut self, mode: RecoveryMode) -> JournalResult<()> {
+        self.poison().check()?;
+        if let Err(e) = self.storage_mut().rewind() {
+            self.poison_mut().set();
+            return Err(JournalError::IoError(e));
+        }
+        match scan_entries(self.storage_mut(), 0, mode) {
+            Ok((_entries, end)) => {
+                self.set_recovered_end_lsn(end);
+                Ok(())
+            }
+            Err(e) => {
+                if let JournalError::IoError(_) = &e {
+                    self.poison_mut().set();
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Implementation-specific append: serialize `obj` and frame it into
+    /// storage with [`write_entry`]. Called by [`Journal::append`], which
+    /// wraps this with the poison check/latch described on
+    /// [`Journal::is_poisoned`] — implementations should not duplicate that
+    /// logic themselves.
+    fn append_raw(&mut self, obj: impl Serializable) -> JournalResult<()>;
+
+    /// append a new journal entry, and then write to it. Short-circuits with
+    /// `JournalError::PreviousIo` if the journal is already poisoned, and
+    /// latches poison if the underlying write fails with an `io::Error`.
+    fn append(&mut self, obj: impl Serializable) -> JournalResult<()> {
+        self.poison().check()?;
+        let result = self.append_raw(obj);
+        if let Err(JournalError::IoError(_)) = &result {
+            self.poison_mut().set();
+        }
+        result
+    }
+
+    /// Implementation-specific prefix drop. Called by
+    /// [`Journal::drop_prefix`], which wraps this with the same poison
+    /// check/latch as [`Journal::append`].
+    fn drop_prefix_raw(&mut self, up_to: Lsn) -> JournalResult<()>;
+
+    /// drop the journal's prefix. Short-circuits with
+    /// `JournalError::PreviousIo` if the journal is already poisoned, and
+    /// latches poison if the underlying compaction fails with an
+    /// `io::Error`.
+    fn drop_prefix(&mut self, up_to: Lsn) -> JournalResult<()> {
+        self.poison().check()?;
+        let result = self.drop_prefix_raw(up_to);
+        if let Err(JournalError::IoError(_)) = &result {
+            self.poison_mut().set();
+        }
+        result
+    }
+}
+
+/// Minimal in-memory implementation of [`Journal`]: storage lives entirely
+/// in a `Vec<u8>` (via a [`Cursor`](io::Cursor)) rather than a real file,
+/// but entries are framed with the same [`write_entry`]/[`scan_entries`]
+/// routines a file-backed implementation would use. Useful as a reference
+/// implementation, and anywhere a journal is needed without touching disk.
+#[derive(Debug)]
+pub struct BufferJournal {
+    id: JournalId,
+    storage: io::Cursor<Vec<u8>>,
+    recovered_end: Lsn,
+    poison: PoisonFlag,
+}
+
+impl Scannable for BufferJournal {}
+impl Syncable for BufferJournal {}
+
+impl Journal for BufferJournal {
+    type Storage = io::Cursor<Vec<u8>>;
+
+    fn open(id: JournalId) -> JournalResult<Self> {
+        let mut journal = Self {
+            id,
+            storage: io::Cursor::new(Vec::new()),
+            recovered_end: 0,
+            poison: PoisonFlag::default(),
+        };
+        journal.recover(RecoveryMode::BestEffort)?;
+        Ok(journal)
+    }
+
+    fn id(&self) -> JournalId {
+        self.id
+    }
+
+    fn storage_mut(&mut self) -> &mut Self::Storage {
+        &mut self.storage
+    }
+
+    fn poison(&self) -> &PoisonFlag {
+        &self.poison
+    }
+
+    fn poison_mut(&mut self) -> &mut PoisonFlag {
+        &mut self.poison
+    }
+
+    fn recovered_end_lsn(&self) -> Lsn {
+        self.recovered_end
+    }
+
+    fn set_recovered_end_lsn(&mut self, lsn: Lsn) {
+        self.recovered_end = lsn;
+    }
+
+    fn append_raw(&mut self, obj: impl Serializable) -> JournalResult<()> {
+        let mut payload = Vec::new();
+        obj.serialize_into(&mut payload)
+            .map_err(JournalError::SerializationError)?;
+
+        self.storage
+            .seek(io::SeekFrom::End(0))
+            .map_err(JournalError::IoError)?;
+        write_entry(&mut self.storage, &payload).map_err(JournalError::IoError)?;
+        self.recovered_end += 1;
+        Ok(())
+    }
+
+    fn drop_prefix_raw(&mut self, up_to: Lsn) -> JournalResult<()> {
+        self.storage.rewind().map_err(JournalError::IoError)?;
+        let (entries, end) = scan_entries(&mut self.storage, 0, RecoveryMode::Strict)?;
 
-    /// drop the journal's prefix
-    fn drop_prefix(&mut self, up_to: Lsn) -> JournalResult<()>;
+        let keep_from = up_to.min(end) as usize;
+        let mut rebuilt = Vec::new();
+        for payload in &entries[keep_from..] {
+            write_entry(&mut rebuilt, payload).map_err(JournalError::IoError)?;
+        }
+        self.storage = io::Cursor::new(rebuilt);
+        Ok(())
+    }
 }
\ No newline at end of file
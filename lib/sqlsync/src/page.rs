@@ -2,6 +2,7 @@ use std::{
     collections::BTreeMap,
     io::{self, Write},
     mem::size_of,
+    ops::Range,
 };
 
 use crate::{positioned_io::PositionedReader, Serializable};
@@ -13,93 +14,420 @@ const PAGE_IDX_SIZE: usize = size_of::<PageIdx>();
 
 pub type Page = [u8; PAGESIZE];
 
+/// A content digest identifying a unique page body, used by the
+/// content-addressed storage mode below.
+pub type PageDigest = [u8; 32];
+const PAGE_DIGEST_SIZE: usize = size_of::<PageDigest>();
+
+fn digest(page: &Page) -> PageDigest {
+    blake3::hash(page).into()
+}
+
+/// The format tag written as the first byte of a serialized `SparsePages`,
+/// distinguishing the plain layout from the content-addressed one.
+const FORMAT_INLINE: u8 = 0;
+const FORMAT_DEDUPED: u8 = 1;
+
+/// `format (1) + max_page_idx (8) + min_page_idx (8) + num_intervals (4)`,
+/// i.e. everything before the (variable-length) interval table.
+const FIXED_HEADER_LEN: usize = 1 + PAGE_IDX_SIZE + PAGE_IDX_SIZE + 4;
+/// Each covered interval is `[start, end)` as two `PageIdx`s.
+const INTERVAL_SIZE: usize = PAGE_IDX_SIZE + PAGE_IDX_SIZE;
+
+/// Merge a sorted, deduplicated sequence of page indices into the minimal
+/// set of contiguous `[start, end)` runs that cover them. Storing this
+/// alongside `min_page_idx`/`max_page_idx` lets a reader skip a layer that
+/// has a wide min/max but only sparsely covers the pages in between.
+fn merge_intervals(sorted_idxs: impl Iterator<Item = PageIdx>) -> Vec<(PageIdx, PageIdx)> {
+    let mut intervals: Vec<(PageIdx, PageIdx)> = Vec::new();
+    for idx in sorted_idxs {
+        match intervals.last_mut() {
+            Some((_, end)) if *end == idx => *end = idx + 1,
+            _ => intervals.push((idx, idx + 1)),
+        }
+    }
+    intervals
+}
+
+#[derive(Debug, Clone)]
+enum PageStore {
+    /// Every page idx maps directly to its body; simplest layout, but a
+    /// journal that rewrites the same page contents repeatedly (or many
+    /// journals sharing boilerplate SQLite pages) pays for each copy.
+    Inline(BTreeMap<PageIdx, Page>),
+    /// Each distinct page body is stored once, keyed by its digest; the
+    /// per-page-idx map only stores the digest.
+    Deduped {
+        index: BTreeMap<PageIdx, PageDigest>,
+        bodies: BTreeMap<PageDigest, Page>,
+    },
+}
+
+impl Default for PageStore {
+    fn default() -> Self {
+        PageStore::Inline(BTreeMap::new())
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct SparsePages {
-    pages: BTreeMap<PageIdx, Page>,
+    store: PageStore,
 }
 
 impl SparsePages {
     pub fn new() -> SparsePages {
         Self {
-            pages: BTreeMap::new(),
+            store: PageStore::Inline(BTreeMap::new()),
+        }
+    }
+
+    /// Create a `SparsePages` that dedups page bodies by content digest
+    /// instead of storing every page inline.
+    pub fn new_deduped() -> SparsePages {
+        Self {
+            store: PageStore::Deduped {
+                index: BTreeMap::new(),
+                bodies: BTreeMap::new(),
+            },
         }
     }
 
     pub fn num_pages(&self) -> usize {
-        self.pages.len()
+        match &self.store {
+            PageStore::Inline(pages) => pages.len(),
+            PageStore::Deduped { index, .. } => index.len(),
+        }
+    }
+
+    /// The number of distinct page bodies actually stored. Equal to
+    /// `num_pages()` in inline mode; in deduped mode this is how many pages
+    /// we're actually paying to store, so `num_pages() - unique_pages()` is
+    /// the count of pages that were deduplicated away.
+    pub fn unique_pages(&self) -> usize {
+        match &self.store {
+            PageStore::Inline(pages) => pages.len(),
+            PageStore::Deduped { bodies, .. } => bodies.len(),
+        }
     }
 
     pub fn clear(&mut self) {
-        self.pages.clear();
+        match &mut self.store {
+            PageStore::Inline(pages) => pages.clear(),
+            PageStore::Deduped { index, bodies } => {
+                index.clear();
+                bodies.clear();
+            }
+        }
     }
 
     pub fn write(&mut self, page_idx: PageIdx, page: Page) {
-        self.pages.insert(page_idx, page);
+        match &mut self.store {
+            PageStore::Inline(pages) => {
+                pages.insert(page_idx, page);
+            }
+            PageStore::Deduped { index, bodies } => {
+                let d = digest(&page);
+                bodies.entry(d).or_insert(page);
+                index.insert(page_idx, d);
+            }
+        }
     }
 
     // returns the max page index of this sparse pages object
     pub fn max_page_idx(&self) -> Option<PageIdx> {
-        self.pages.keys().max().copied()
+        match &self.store {
+            PageStore::Inline(pages) => pages.keys().max().copied(),
+            PageStore::Deduped { index, .. } => index.keys().max().copied(),
+        }
+    }
+
+    // returns the min page index of this sparse pages object
+    pub fn min_page_idx(&self) -> Option<PageIdx> {
+        match &self.store {
+            PageStore::Inline(pages) => pages.keys().min().copied(),
+            PageStore::Deduped { index, .. } => index.keys().min().copied(),
+        }
+    }
+
+    fn page_idxs(&self) -> Box<dyn Iterator<Item = PageIdx> + '_> {
+        match &self.store {
+            PageStore::Inline(pages) => Box::new(pages.keys().copied()),
+            PageStore::Deduped { index, .. } => Box::new(index.keys().copied()),
+        }
     }
 
     pub fn read(&self, page_idx: PageIdx, page_offset: usize, buf: &mut [u8]) -> usize {
-        self.pages
-            .get(&page_idx)
-            .map(|page| {
-                let end = page_offset + buf.len();
-                assert!(end <= PAGESIZE, "page offset out of bounds");
-                buf.copy_from_slice(&page[page_offset..end]);
-                buf.len()
-            })
-            .unwrap_or(0)
+        let page = match &self.store {
+            PageStore::Inline(pages) => pages.get(&page_idx),
+            PageStore::Deduped { index, bodies } => index.get(&page_idx).and_then(|d| bodies.get(d)),
+        };
+        page.map(|page| {
+            let end = page_offset + buf.len();
+            assert!(end <= PAGESIZE, "page offset out of bounds");
+            buf.copy_from_slice(&page[page_offset..end]);
+            buf.len()
+        })
+        .unwrap_or(0)
     }
 }
 
 /// The serialized form of SparsePages can be read using the SerializedPagesReader object below
 impl Serializable for SparsePages {
     fn serialize_into<W: Write>(&self, writer: &mut W) -> io::Result<()> {
-        assert!(
-            self.pages.len() > 0,
-            "cannot serialize empty sparse pages obj"
-        );
+        assert!(self.num_pages() > 0, "cannot serialize empty sparse pages obj");
+
+        let intervals = merge_intervals(self.page_idxs());
+        let min_page_idx = intervals.first().expect("cannot serialize empty sparse pages obj").0;
+        let max_page_idx = intervals.last().expect("cannot serialize empty sparse pages obj").1 - 1;
 
-        // serialize the max page idx
-        let max_page_idx = self
-            .max_page_idx()
-            .expect("cannot serialize empty sparse pages obj");
-        writer.write_all(&max_page_idx.to_be_bytes())?;
+        let write_fixed_header = |writer: &mut W, format: u8| -> io::Result<()> {
+            writer.write_all(&[format])?;
+            writer.write_all(&max_page_idx.to_be_bytes())?;
+            writer.write_all(&min_page_idx.to_be_bytes())?;
+            writer.write_all(&(intervals.len() as u32).to_be_bytes())?;
+            for (start, end) in intervals.iter() {
+                writer.write_all(&start.to_be_bytes())?;
+                writer.write_all(&end.to_be_bytes())?;
+            }
+            Ok(())
+        };
 
-        // serialize the pages, sorted by page_idx
-        for (page_idx, page) in self.pages.iter() {
-            writer.write_all(&page_idx.to_be_bytes())?;
-            writer.write_all(&page[..])?;
+        match &self.store {
+            PageStore::Inline(pages) => {
+                write_fixed_header(writer, FORMAT_INLINE)?;
+
+                // serialize the pages, sorted by page_idx
+                for (page_idx, page) in pages.iter() {
+                    writer.write_all(&page_idx.to_be_bytes())?;
+                    writer.write_all(&page[..])?;
+                }
+            }
+            PageStore::Deduped { index, bodies } => {
+                write_fixed_header(writer, FORMAT_DEDUPED)?;
+
+                // digest -> body table, sorted by digest
+                writer.write_all(&(bodies.len() as u32).to_be_bytes())?;
+                for (d, page) in bodies.iter() {
+                    writer.write_all(d)?;
+                    writer.write_all(&page[..])?;
+                }
+
+                // page_idx -> digest table, sorted by page_idx
+                for (page_idx, d) in index.iter() {
+                    writer.write_all(&page_idx.to_be_bytes())?;
+                    writer.write_all(d)?;
+                }
+            }
         }
 
         Ok(())
     }
 }
 
-/// Layout is:
+/// Layout, inline mode (format byte `FORMAT_INLINE`):
+///    format: u8
 ///    max_page_idx: u64
+///    min_page_idx: u64
+///    num_intervals: u32
+///    intervals: num_intervals * [ start: u64, end: u64 ]
 ///    for each page (sorted by page_idx) [
 ///      page_idx: u64
 ///      page: [u8; PAGESIZE]
 ///    ]
+///
+/// Layout, content-addressed mode (format byte `FORMAT_DEDUPED`):
+///    format: u8
+///    max_page_idx: u64
+///    min_page_idx: u64
+///    num_intervals: u32
+///    intervals: num_intervals * [ start: u64, end: u64 ]
+///    num_digests: u32
+///    for each body (sorted by digest) [
+///      digest: [u8; 32]
+///      page: [u8; PAGESIZE]
+///    ]
+///    for each page (sorted by page_idx) [
+///      page_idx: u64
+///      digest: [u8; 32]
+///    ]
+///
+/// `min_page_idx`/`max_page_idx` let a reader cheaply skip this layer
+/// entirely when it's resolving a `PageIdx` outside `page_range()`; the
+/// interval table additionally lets it skip a layer whose min/max span is
+/// wide but whose actual coverage is sparse.
 pub struct SerializedPagesReader<R: PositionedReader>(pub R);
 
 impl<R: PositionedReader> SerializedPagesReader<R> {
+    fn format(&self) -> io::Result<u8> {
+        let mut buf = [0u8; 1];
+        self.0.read_exact_at(0, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn num_intervals(&self) -> io::Result<usize> {
+        let mut buf = [0u8; 4];
+        self.0
+            .read_exact_at(1 + PAGE_IDX_SIZE + PAGE_IDX_SIZE, &mut buf)?;
+        Ok(u32::from_be_bytes(buf) as usize)
+    }
+
+    /// Offset of the first byte after the fixed header and interval table,
+    /// i.e. where the format-specific body begins.
+    fn header_len(&self) -> io::Result<usize> {
+        Ok(FIXED_HEADER_LEN + self.num_intervals()? * INTERVAL_SIZE)
+    }
+
+    fn num_digests(&self) -> io::Result<usize> {
+        let mut buf = [0u8; 4];
+        self.0.read_exact_at(self.header_len()?, &mut buf)?;
+        Ok(u32::from_be_bytes(buf) as usize)
+    }
+
+    /// Offset of the page_idx -> digest index table, only valid in deduped mode.
+    fn index_table_offset(&self) -> io::Result<usize> {
+        let num_digests = self.num_digests()?;
+        Ok(self.header_len()? + 4 + num_digests * (PAGE_DIGEST_SIZE + PAGESIZE))
+    }
+
+    /// Offset of the page table, only valid in inline mode.
+    fn inline_table_offset(&self) -> io::Result<usize> {
+        self.header_len()
+    }
+
     pub fn num_pages(&self) -> io::Result<usize> {
         let file_size = self.0.size()?;
-        let num_pages = (file_size - PAGE_IDX_SIZE) / (PAGE_IDX_SIZE + PAGESIZE);
-        Ok(num_pages)
+        match self.format()? {
+            FORMAT_INLINE => {
+                let table_offset = self.inline_table_offset()?;
+                Ok((file_size - table_offset) / (PAGE_IDX_SIZE + PAGESIZE))
+            }
+            FORMAT_DEDUPED => {
+                let index_offset = self.index_table_offset()?;
+                Ok((file_size - index_offset) / (PAGE_IDX_SIZE + PAGE_DIGEST_SIZE))
+            }
+            other => panic!("unknown SparsePages format tag: {other}"),
+        }
     }
 
     pub fn max_page_idx(&self) -> io::Result<PageIdx> {
         let mut buf = [0; PAGE_IDX_SIZE];
-        self.0.read_exact_at(0, &mut buf)?;
+        self.0.read_exact_at(1, &mut buf)?;
+        Ok(PageIdx::from_be_bytes(buf))
+    }
+
+    pub fn min_page_idx(&self) -> io::Result<PageIdx> {
+        let mut buf = [0; PAGE_IDX_SIZE];
+        self.0.read_exact_at(1 + PAGE_IDX_SIZE, &mut buf)?;
         Ok(PageIdx::from_be_bytes(buf))
     }
 
+    /// The `[min_page_idx, max_page_idx]` span covered by this layer, as a
+    /// half-open range. A resolver walking a stack of layers can skip any
+    /// layer whose range doesn't contain the page it's looking for, before
+    /// doing any per-page search within that layer.
+    pub fn page_range(&self) -> io::Result<Range<PageIdx>> {
+        Ok(self.min_page_idx()?..self.max_page_idx()?.saturating_add(1))
+    }
+
+    /// The compact set of contiguous `[start, end)` runs of page indices
+    /// this layer actually covers. For a sparse/scattered layer whose
+    /// `page_range()` is wide but whose real coverage is a handful of small
+    /// runs, this lets a resolver skip the layer without a per-page search.
+    fn intervals(&self) -> io::Result<Vec<Range<PageIdx>>> {
+        let num_intervals = self.num_intervals()?;
+        // num_intervals comes straight off the wire; bound the allocation by
+        // what the file could actually hold rather than trusting it outright.
+        if FIXED_HEADER_LEN + num_intervals * INTERVAL_SIZE > self.0.size()? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "interval table claims more entries than the file could hold",
+            ));
+        }
+        let mut intervals = Vec::with_capacity(num_intervals);
+        let mut buf = [0u8; INTERVAL_SIZE];
+        for i in 0..num_intervals {
+            let offset = FIXED_HEADER_LEN + i * INTERVAL_SIZE;
+            self.0.read_exact_at(offset, &mut buf)?;
+            let start = PageIdx::from_be_bytes(buf[0..PAGE_IDX_SIZE].try_into().unwrap());
+            let end = PageIdx::from_be_bytes(buf[PAGE_IDX_SIZE..].try_into().unwrap());
+            intervals.push(start..end);
+        }
+        Ok(intervals)
+    }
+
+    /// Whether `page_idx` could possibly be present in this layer: cheaper
+    /// than `read`, since it only consults the header's min/max and interval
+    /// table rather than searching the page table itself. A `false` result
+    /// is definitive; a `true` result means the page *might* be present and
+    /// `read` should still be called to confirm.
+    pub fn might_contain(&self, page_idx: PageIdx) -> io::Result<bool> {
+        if !self.page_range()?.contains(&page_idx) {
+            return Ok(false);
+        }
+        Ok(self
+            .intervals()?
+            .iter()
+            .any(|interval| interval.contains(&page_idx)))
+    }
+
+    /// Look up the digest stored for `page_idx`, if present, by binary
+    /// searching the page_idx -> digest table.
+    fn lookup_digest(&self, page_idx: PageIdx) -> io::Result<Option<PageDigest>> {
+        let index_offset = self.index_table_offset()?;
+        let num_pages = (self.0.size()? - index_offset) / (PAGE_IDX_SIZE + PAGE_DIGEST_SIZE);
+
+        let mut left: usize = 0;
+        let mut right: usize = num_pages;
+        let mut page_idx_buf = [0; PAGE_IDX_SIZE];
+
+        while left < right {
+            let mid = left + (right - left) / 2;
+            let mid_offset = index_offset + mid * (PAGE_IDX_SIZE + PAGE_DIGEST_SIZE);
+            self.0.read_exact_at(mid_offset, &mut page_idx_buf)?;
+            let mid_idx = PageIdx::from_be_bytes(page_idx_buf);
+
+            if mid_idx == page_idx {
+                let mut d = [0u8; PAGE_DIGEST_SIZE];
+                self.0
+                    .read_exact_at(mid_offset + PAGE_IDX_SIZE, &mut d)?;
+                return Ok(Some(d));
+            } else if mid_idx < page_idx {
+                left = mid + 1;
+            } else {
+                right = mid;
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Find the body offset (of the page data, not the digest) for `digest`
+    /// by binary searching the digest -> body table.
+    fn lookup_body_offset(&self, digest: &PageDigest) -> io::Result<Option<usize>> {
+        let num_digests = self.num_digests()?;
+        let table_start = self.header_len()? + 4;
+
+        let mut left: usize = 0;
+        let mut right: usize = num_digests;
+        let mut digest_buf = [0u8; PAGE_DIGEST_SIZE];
+
+        while left < right {
+            let mid = left + (right - left) / 2;
+            let mid_offset = table_start + mid * (PAGE_DIGEST_SIZE + PAGESIZE);
+            self.0.read_exact_at(mid_offset, &mut digest_buf)?;
+
+            if &digest_buf == digest {
+                return Ok(Some(mid_offset + PAGE_DIGEST_SIZE));
+            } else if digest_buf.as_slice() < digest.as_slice() {
+                left = mid + 1;
+            } else {
+                right = mid;
+            }
+        }
+
+        Ok(None)
+    }
+
     pub fn read(&self, page_idx: PageIdx, page_offset: usize, buf: &mut [u8]) -> io::Result<usize> {
         assert!(page_offset < PAGESIZE, "page_offset must be < PAGESIZE");
         assert!(
@@ -107,7 +435,26 @@ impl<R: PositionedReader> SerializedPagesReader<R> {
             "refusing to read more than one page"
         );
 
-        let num_pages = self.num_pages()?;
+        match self.format()? {
+            FORMAT_INLINE => self.read_inline(page_idx, page_offset, buf),
+            FORMAT_DEDUPED => self.read_deduped(page_idx, page_offset, buf),
+            other => panic!("unknown SparsePages format tag: {other}"),
+        }
+    }
+
+    fn read_inline(&self, page_idx: PageIdx, page_offset: usize, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inline_body_offset(page_idx)? {
+            Some(offset) => {
+                self.0.read_exact_at(offset + page_offset, buf)?;
+                Ok(buf.len())
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn inline_body_offset(&self, page_idx: PageIdx) -> io::Result<Option<usize>> {
+        let table_offset = self.inline_table_offset()?;
+        let num_pages = (self.0.size()? - table_offset) / (PAGE_IDX_SIZE + PAGESIZE);
 
         let mut left: usize = 0;
         let mut right: usize = num_pages;
@@ -115,15 +462,13 @@ impl<R: PositionedReader> SerializedPagesReader<R> {
 
         while left < right {
             let mid = left + (right - left) / 2;
-            let mid_offset = PAGE_IDX_SIZE + (mid * (PAGE_IDX_SIZE + PAGESIZE));
+            let mid_offset = table_offset + (mid * (PAGE_IDX_SIZE + PAGESIZE));
             self.0.read_exact_at(mid_offset, &mut page_idx_buf)?;
 
             let mid_idx = PageIdx::from_be_bytes(page_idx_buf);
 
             if mid_idx == page_idx {
-                let read_start = mid_offset + PAGE_IDX_SIZE + page_offset;
-                self.0.read_exact_at(read_start, buf)?;
-                return Ok(buf.len());
+                return Ok(Some(mid_offset + PAGE_IDX_SIZE));
             } else if mid_idx < page_idx {
                 left = mid + 1;
             } else {
@@ -131,6 +476,121 @@ impl<R: PositionedReader> SerializedPagesReader<R> {
             }
         }
 
-        Ok(0)
+        Ok(None)
+    }
+
+    /// Two lookups: page_idx -> digest, then digest -> body.
+    fn read_deduped(&self, page_idx: PageIdx, page_offset: usize, buf: &mut [u8]) -> io::Result<usize> {
+        match self.deduped_body_offset(page_idx)? {
+            Some(offset) => {
+                self.0.read_exact_at(offset + page_offset, buf)?;
+                Ok(buf.len())
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn deduped_body_offset(&self, page_idx: PageIdx) -> io::Result<Option<usize>> {
+        let d = match self.lookup_digest(page_idx)? {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        self.lookup_body_offset(&d)
+    }
+
+    /// Scan the header once and materialize the sorted `page_idx -> file
+    /// offset of the page body` map in memory, so repeated reads of this
+    /// layer (the common case on a VFS hot path) become a single in-memory
+    /// lookup plus one positioned read, instead of a fresh binary search
+    /// with its own positioned reads per probe every time.
+    pub fn with_index(self) -> io::Result<IndexedPagesReader<R>> {
+        let num_pages = self.num_pages()?;
+        let mut offsets = Vec::with_capacity(num_pages);
+
+        match self.format()? {
+            FORMAT_INLINE => {
+                let table_offset = self.inline_table_offset()?;
+                for i in 0..num_pages {
+                    let mid_offset = table_offset + (i * (PAGE_IDX_SIZE + PAGESIZE));
+                    let mut page_idx_buf = [0; PAGE_IDX_SIZE];
+                    self.0.read_exact_at(mid_offset, &mut page_idx_buf)?;
+                    let page_idx = PageIdx::from_be_bytes(page_idx_buf);
+                    offsets.push((page_idx, mid_offset + PAGE_IDX_SIZE));
+                }
+            }
+            FORMAT_DEDUPED => {
+                let index_offset = self.index_table_offset()?;
+                for i in 0..num_pages {
+                    let mid_offset = index_offset + i * (PAGE_IDX_SIZE + PAGE_DIGEST_SIZE);
+                    let mut page_idx_buf = [0; PAGE_IDX_SIZE];
+                    self.0.read_exact_at(mid_offset, &mut page_idx_buf)?;
+                    let page_idx = PageIdx::from_be_bytes(page_idx_buf);
+
+                    let mut d = [0u8; PAGE_DIGEST_SIZE];
+                    self.0.read_exact_at(mid_offset + PAGE_IDX_SIZE, &mut d)?;
+                    let body_offset = self
+                        .lookup_body_offset(&d)?
+                        .expect("digest referenced by page index table must exist in digest table");
+                    offsets.push((page_idx, body_offset));
+                }
+            }
+            other => panic!("unknown SparsePages format tag: {other}"),
+        }
+
+        Ok(IndexedPagesReader {
+            reader: self.0,
+            offsets,
+        })
     }
-}
\ No newline at end of file
+}
+
+/// A `SerializedPagesReader` whose `page_idx -> file offset` table has been
+/// loaded into memory, via [`SerializedPagesReader::with_index`]. Use this
+/// when the same layer is read from repeatedly; use the plain
+/// `SerializedPagesReader` for one-shot/streaming access.
+pub struct IndexedPagesReader<R: PositionedReader> {
+    reader: R,
+    /// Sorted by `PageIdx`, matching on-disk order.
+    offsets: Vec<(PageIdx, usize)>,
+}
+
+impl<R: PositionedReader> IndexedPagesReader<R> {
+    fn body_offset(&self, page_idx: PageIdx) -> Option<usize> {
+        self.offsets
+            .binary_search_by_key(&page_idx, |(idx, _)| *idx)
+            .ok()
+            .map(|i| self.offsets[i].1)
+    }
+
+    /// The `[min_page_idx, max_page_idx]` span covered by this layer, as a
+    /// half-open range, read off the in-memory index rather than the file.
+    pub fn page_range(&self) -> Range<PageIdx> {
+        match (self.offsets.first(), self.offsets.last()) {
+            (Some((min, _)), Some((max, _))) => *min..*max + 1,
+            _ => 0..0,
+        }
+    }
+
+    /// Whether this layer holds `page_idx` at all, without reading its body.
+    /// Useful when walking a stack of snapshots to find the topmost layer
+    /// holding a page.
+    pub fn contains(&self, page_idx: PageIdx) -> bool {
+        self.body_offset(page_idx).is_some()
+    }
+
+    pub fn read(&self, page_idx: PageIdx, page_offset: usize, buf: &mut [u8]) -> io::Result<usize> {
+        assert!(page_offset < PAGESIZE, "page_offset must be < PAGESIZE");
+        assert!(
+            page_offset + buf.len() <= PAGESIZE,
+            "refusing to read more than one page"
+        );
+
+        match self.body_offset(page_idx) {
+            Some(offset) => {
+                self.reader.read_exact_at(offset + page_offset, buf)?;
+                Ok(buf.len())
+            }
+            None => Ok(0),
+        }
+    }
+}
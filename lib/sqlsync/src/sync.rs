@@ -0,0 +1,119 @@
+//! Wire protocol for syncing `Journal<T>`s between a client and a server.
+//!
+//! The protocol is deliberately dumb: whichever side is behind pulls from
+//! whichever side is ahead. A session multiplexes many journals (identified
+//! by `JournalId`) over a single logical connection. There is no shared
+//! clock between replicas, so ordering is enforced purely by LSN: `write`
+//! rejects a batch that would leave a gap, and silently (and correctly)
+//! no-ops on the overlapping prefix of a batch that arrived twice.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use thiserror::Error;
+
+use crate::journal::{Batch, Cursor, Journal, JournalId, WriteError, LSN};
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("unknown journal id: {0}")]
+    UnknownJournal(JournalId),
+
+    #[error("cursor at lsn {lsn} is outside journal range {range:?}")]
+    CursorOutOfRange { lsn: LSN, range: Range<LSN> },
+
+    #[error(transparent)]
+    Write(#[from] WriteError),
+}
+
+/// Sent by the side initiating a sync to say what it already has.
+pub struct Hello {
+    pub id: JournalId,
+    /// The LSN the sender wants to resume from (i.e. their `end()` cursor).
+    pub cursor: LSN,
+}
+
+/// Sent in reply, describing what the other side has.
+pub struct HelloAck {
+    pub id: JournalId,
+    pub range: Range<LSN>,
+}
+
+/// Multiplexes sync for many journals over one connection.
+pub struct SyncSession<T>
+where
+    T: Clone,
+{
+    journals: HashMap<JournalId, Journal<T>>,
+}
+
+impl<T> SyncSession<T>
+where
+    T: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            journals: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, journal: Journal<T>) {
+        self.journals.insert(journal.id(), journal);
+    }
+
+    fn journal(&self, id: JournalId) -> Result<&Journal<T>, SyncError> {
+        self.journals.get(&id).ok_or(SyncError::UnknownJournal(id))
+    }
+
+    fn journal_mut(&mut self, id: JournalId) -> Result<&mut Journal<T>, SyncError> {
+        self.journals
+            .get_mut(&id)
+            .ok_or(SyncError::UnknownJournal(id))
+    }
+
+    /// Handle an incoming `Hello` for one of our journals, replying with
+    /// where we are so the peer can figure out who's behind.
+    pub fn handshake(&self, hello: &Hello) -> Result<HelloAck, SyncError> {
+        let journal = self.journal(hello.id)?;
+        Ok(HelloAck {
+            id: journal.id(),
+            range: journal.range(),
+        })
+    }
+
+    /// Pull up to `max_len` entries starting at `cursor` from one of our journals.
+    /// A peer-supplied `cursor` outside the journal's current range is a
+    /// `SyncError`, not a panic: `Journal::read` asserts on its cursor, so we
+    /// validate here before ever reaching it.
+    pub fn read(&self, id: JournalId, cursor: Cursor, max_len: usize) -> Result<Batch<'_, T>, SyncError> {
+        let journal = self.journal(id)?;
+        let range = journal.range();
+        if !range.contains(&cursor.lsn()) {
+            return Err(SyncError::CursorOutOfRange {
+                lsn: cursor.lsn(),
+                range,
+            });
+        }
+        Ok(journal.read(cursor, max_len))
+    }
+
+    /// Apply a batch received from the peer. There's no shared clock to
+    /// compare versions across replicas, so staleness/duplication is handled
+    /// entirely by `write`'s own LSN-range logic: a batch that starts past
+    /// our end is a gap (`SyncError::Write`), and a batch that overlaps what
+    /// we already have is a no-op for that overlapping prefix rather than an
+    /// error, so redelivering the same batch twice is `Ok`.
+    pub fn apply(&mut self, id: JournalId, batch: Batch<T>) -> Result<(), SyncError> {
+        self.journal_mut(id)?.write(batch)?;
+        Ok(())
+    }
+}
+
+impl<T> Default for SyncSession<T>
+where
+    T: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}